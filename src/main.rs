@@ -1,13 +1,20 @@
+use std::collections::VecDeque;
 use std::env;
 use std::ffi::OsString;
 use std::fs;
 use std::io;
 use std::io::IsTerminal;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
 
 use clap::{Parser, ValueEnum};
+use git2::{Repository, Status, StatusOptions};
 use pathdiff::diff_paths;
+use serde::Serialize;
 use terminal_size::{terminal_size, Width};
 
 #[derive(Parser, Debug)]
@@ -34,10 +41,40 @@ struct Cli {
     #[arg(long)]
     max_depth: Option<usize>,
 
+    /// Descend into repositories found during discovery instead of stopping at the first .git,
+    /// surfacing nested repos and the submodules declared in the root's .gitmodules
+    /// (including uninitialized ones)
+    #[arg(long)]
+    nested: bool,
+
     /// List discovered repositories without executing git
     #[arg(long)]
     list: bool,
 
+    /// Print an aligned status table (branch, ahead/behind, dirty counts) instead of running git
+    #[arg(long)]
+    summary: bool,
+
+    /// Only operate on repos with uncommitted or untracked changes
+    #[arg(long)]
+    if_dirty: bool,
+
+    /// Only operate on repos with no uncommitted or untracked changes
+    #[arg(long)]
+    if_clean: bool,
+
+    /// Only operate on repos whose current branch name matches this glob (`*` and `?`)
+    #[arg(long, value_name = "GLOB")]
+    on_branch: Option<String>,
+
+    /// Only operate on repos that are ahead of their upstream
+    #[arg(long)]
+    if_ahead: bool,
+
+    /// Only operate on repos that are behind their upstream
+    #[arg(long)]
+    if_behind: bool,
+
     /// Heading style for repository separators
     #[arg(long, value_enum, default_value_t = HeadingStyle::Rule)]
     heading_style: HeadingStyle,
@@ -50,11 +87,48 @@ struct Cli {
     #[arg(long)]
     no_heading: bool,
 
+    /// Run across repos concurrently ("auto" = available parallelism)
+    #[arg(long, default_value = "1")]
+    jobs: Jobs,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
     /// Git command and args (first token is the git subcommand)
     #[arg(value_name = "GIT_ARGS", trailing_var_arg = true)]
     git_args: Vec<OsString>,
 }
 
+#[derive(Copy, Clone, Debug)]
+enum Jobs {
+    Auto,
+    Count(usize),
+}
+
+impl std::str::FromStr for Jobs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            Ok(Jobs::Auto)
+        } else {
+            s.parse::<usize>()
+                .map(Jobs::Count)
+                .map_err(|_| format!("invalid --jobs value: {s}"))
+        }
+    }
+}
+
+impl Jobs {
+    fn resolve(self) -> usize {
+        match self {
+            Jobs::Auto => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            Jobs::Count(n) => n.max(1),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, ValueEnum)]
 enum HeadingStyle {
     Plain,
@@ -68,6 +142,21 @@ enum ColorMode {
     Never,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// One repo's captured result, for `--format json`.
+#[derive(Serialize)]
+struct RepoResult {
+    path: String,
+    exit_code: i32,
+    stdout: String,
+    stderr: String,
+}
+
 fn is_git_repo_dir(dir: &Path) -> bool {
     let git_path = dir.join(".git");
     match fs::symlink_metadata(&git_path) {
@@ -89,17 +178,20 @@ fn ancestors_with_git(mut dir: PathBuf) -> Vec<PathBuf> {
     repos
 }
 
-fn discover_children(root: &Path, max_depth: Option<usize>) -> io::Result<Vec<PathBuf>> {
+fn discover_children(root: &Path, max_depth: Option<usize>, nested: bool) -> io::Result<Vec<PathBuf>> {
     let mut repos = Vec::new();
     fn walk(
         dir: &Path,
         depth: usize,
         max_depth: Option<usize>,
+        nested: bool,
         out: &mut Vec<PathBuf>,
     ) -> io::Result<()> {
         if is_git_repo_dir(dir) {
             out.push(dir.to_path_buf());
-            return Ok(()); // first occurrence rule: do not descend further
+            if !nested {
+                return Ok(()); // first occurrence rule: do not descend further
+            }
         }
 
         if let Some(max) = max_depth {
@@ -139,17 +231,63 @@ fn discover_children(root: &Path, max_depth: Option<usize>) -> io::Result<Vec<Pa
             if sub.file_name().map(|n| n == ".git").unwrap_or(false) {
                 continue;
             }
-            walk(&sub, depth + 1, max_depth, out)?;
+            walk(&sub, depth + 1, max_depth, nested, out)?;
         }
         Ok(())
     }
 
-    walk(root, 0, max_depth, &mut repos)?;
-    // Sort lexicographically for stable output
+    walk(root, 0, max_depth, nested, &mut repos)?;
+    if nested {
+        repos.extend(submodule_paths(root));
+    }
+    // Sort lexicographically for stable output; this also orders parents before children
     repos.sort();
+    repos.dedup();
     Ok(repos)
 }
 
+/// Declared submodule worktree paths from the root repository's top-level
+/// `.gitmodules`, so uninitialized submodules (no `.git` entry yet) are
+/// still surfaced under `--nested`.
+fn submodule_paths(root: &Path) -> Vec<PathBuf> {
+    let content = match fs::read_to_string(root.join(".gitmodules")) {
+        Ok(c) => c,
+        Err(_) => return Vec::new(),
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            line.strip_prefix("path")?
+                .trim_start()
+                .strip_prefix('=')
+                .map(|value| root.join(value.trim()))
+        })
+        .collect()
+}
+
+/// Like `heading_for`, but without the trailing `/` used for display
+/// headings — for machine-readable output such as `--format json`.
+fn json_path_label(path: &Path, root_for_rel: &Path, absolute: bool) -> String {
+    if absolute {
+        match path.canonicalize() {
+            Ok(p) => p.display().to_string(),
+            Err(_) => path.display().to_string(),
+        }
+    } else {
+        match diff_paths(path, root_for_rel) {
+            Some(rel) => {
+                if rel.as_os_str().is_empty() {
+                    ".".to_string()
+                } else {
+                    rel.display().to_string()
+                }
+            }
+            None => path.display().to_string(),
+        }
+    }
+}
+
 fn heading_for(path: &Path, root_for_rel: &Path, absolute: bool) -> String {
     if absolute {
         match path.canonicalize() {
@@ -170,14 +308,18 @@ fn heading_for(path: &Path, root_for_rel: &Path, absolute: bool) -> String {
     }
 }
 
-fn colorize(s: &str, color: bool) -> String {
+fn colorize_code(s: &str, code: &str, color: bool) -> String {
     if color {
-        format!("\x1b[1;36m{}\x1b[0m", s)
+        format!("\x1b[{}m{}\x1b[0m", code, s)
     } else {
         s.to_string()
     }
 }
 
+fn colorize(s: &str, color: bool) -> String {
+    colorize_code(s, "1;36", color)
+}
+
 fn print_heading(_index: usize, _total: usize, text: &str, style: HeadingStyle, color: bool) {
     let label = colorize(text, color);
     match style {
@@ -202,6 +344,224 @@ fn print_fence(style: HeadingStyle, color: bool) {
     println!("{}", colorize(&fence, color));
 }
 
+/// Current branch name, or the short hash of a detached HEAD.
+fn branch_label(repo: &Repository) -> String {
+    match repo.head() {
+        Ok(head) => {
+            if head.is_branch() {
+                head.shorthand().unwrap_or("HEAD").to_string()
+            } else {
+                match head.target() {
+                    Some(oid) => oid.to_string().chars().take(7).collect(),
+                    None => "HEAD".to_string(),
+                }
+            }
+        }
+        Err(_) => "(no commits)".to_string(),
+    }
+}
+
+/// `(ahead, behind)` commit counts of the current branch vs. its upstream,
+/// or `None` if HEAD is detached or has no configured upstream.
+fn ahead_behind(repo: &Repository) -> Option<(usize, usize)> {
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    let branch_ref = head.name()?;
+    let upstream_name = repo.branch_upstream_name(branch_ref).ok()?;
+    let upstream_ref = repo.find_reference(upstream_name.as_str()?).ok()?;
+    repo.graph_ahead_behind(head.target()?, upstream_ref.target()?)
+        .ok()
+}
+
+/// `(staged, modified, untracked)` entry counts from the repo's worktree status.
+fn status_counts(repo: &Repository) -> (usize, usize, usize) {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true).include_ignored(false);
+    let statuses = match repo.statuses(Some(&mut opts)) {
+        Ok(s) => s,
+        Err(_) => return (0, 0, 0),
+    };
+
+    let (mut staged, mut modified, mut untracked) = (0, 0, 0);
+    for entry in statuses.iter() {
+        let s = entry.status();
+        if s.intersects(
+            Status::INDEX_NEW
+                | Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE,
+        ) {
+            staged += 1;
+        }
+        if s.intersects(
+            Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE,
+        ) {
+            modified += 1;
+        }
+        if s.contains(Status::WT_NEW) {
+            untracked += 1;
+        }
+    }
+    (staged, modified, untracked)
+}
+
+/// Minimal glob matcher supporting `*` (any run of characters, including
+/// none) and `?` (exactly one character) — enough for branch patterns like
+/// `feature/*` or `release-?.x`.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn rec(p: &[char], t: &[char]) -> bool {
+        match p.first() {
+            None => t.is_empty(),
+            Some('*') => rec(&p[1..], t) || (!t.is_empty() && rec(p, &t[1..])),
+            Some('?') => !t.is_empty() && rec(&p[1..], &t[1..]),
+            Some(c) => !t.is_empty() && t[0] == *c && rec(&p[1..], &t[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    rec(&pattern, &text)
+}
+
+/// Whether `repo` satisfies every requested `--if-*`/`--on-branch` predicate.
+/// Returns `true` unmodified when no predicate flag was given.
+fn matches_filters(repo: &Path, cli: &Cli) -> bool {
+    let any_filter =
+        cli.if_dirty || cli.if_clean || cli.on_branch.is_some() || cli.if_ahead || cli.if_behind;
+    if !any_filter {
+        return true;
+    }
+
+    let handle = match Repository::open(repo) {
+        Ok(r) => r,
+        Err(_) => return false,
+    };
+
+    if cli.if_dirty || cli.if_clean {
+        let (staged, modified, untracked) = status_counts(&handle);
+        let dirty = staged > 0 || modified > 0 || untracked > 0;
+        if cli.if_dirty && !dirty {
+            return false;
+        }
+        if cli.if_clean && dirty {
+            return false;
+        }
+    }
+
+    if let Some(pattern) = &cli.on_branch {
+        if !glob_match(pattern, &branch_label(&handle)) {
+            return false;
+        }
+    }
+
+    if cli.if_ahead || cli.if_behind {
+        let (ahead, behind) = ahead_behind(&handle).unwrap_or((0, 0));
+        if cli.if_ahead && ahead == 0 {
+            return false;
+        }
+        if cli.if_behind && behind == 0 {
+            return false;
+        }
+    }
+
+    true
+}
+
+struct SummaryRow {
+    heading: String,
+    branch: String,
+    dirty: bool,
+    ahead: Option<usize>,
+    behind: Option<usize>,
+    staged: usize,
+    modified: usize,
+    untracked: usize,
+}
+
+fn ahead_behind_label(row: &SummaryRow) -> String {
+    match (row.ahead, row.behind) {
+        (Some(a), Some(b)) => format!("+{}/-{}", a, b),
+        _ => "-".to_string(),
+    }
+}
+
+/// Print a compact, fixed-width status table for `repos` using `git2`
+/// instead of spawning a `git status` subprocess per repo.
+fn print_summary(repos: &[PathBuf], root: &Path, absolute: bool, use_color: bool) -> io::Result<()> {
+    let mut rows = Vec::with_capacity(repos.len());
+    for repo_path in repos {
+        let heading = heading_for(repo_path, root, absolute);
+        let repo = match Repository::open(repo_path) {
+            Ok(r) => r,
+            Err(_) => {
+                rows.push(SummaryRow {
+                    heading,
+                    branch: "(error)".to_string(),
+                    dirty: false,
+                    ahead: None,
+                    behind: None,
+                    staged: 0,
+                    modified: 0,
+                    untracked: 0,
+                });
+                continue;
+            }
+        };
+        let (staged, modified, untracked) = status_counts(&repo);
+        let (ahead, behind) = ahead_behind(&repo).map_or((None, None), |(a, b)| (Some(a), Some(b)));
+        rows.push(SummaryRow {
+            heading,
+            branch: branch_label(&repo),
+            dirty: staged > 0 || modified > 0 || untracked > 0,
+            ahead,
+            behind,
+            staged,
+            modified,
+            untracked,
+        });
+    }
+
+    let path_w = rows
+        .iter()
+        .map(|r| r.heading.len())
+        .max()
+        .unwrap_or(0)
+        .max("repository".len());
+    let branch_w = rows
+        .iter()
+        .map(|r| r.branch.len())
+        .max()
+        .unwrap_or(0)
+        .max("branch".len());
+    let ab_w = rows
+        .iter()
+        .map(ahead_behind_label)
+        .map(|s| s.len())
+        .max()
+        .unwrap_or(0)
+        .max("ahead/behind".len());
+
+    println!(
+        "{:path_w$}  {:branch_w$}  {:ab_w$}  {:>6}  {:>8}  {:>10}",
+        "repository", "branch", "ahead/behind", "staged", "modified", "untracked",
+    );
+
+    for row in &rows {
+        let ab = ahead_behind_label(row);
+        let branch_code = if row.dirty { "1;31" } else { "1;32" };
+        let branch_colored = colorize_code(&row.branch, branch_code, use_color);
+        let branch_pad = " ".repeat(branch_w.saturating_sub(row.branch.len()));
+        println!(
+            "{:path_w$}  {branch_colored}{branch_pad}  {ab:ab_w$}  {:>6}  {:>8}  {:>10}",
+            row.heading, row.staged, row.modified, row.untracked,
+        );
+    }
+
+    Ok(())
+}
+
 fn run_git_in(repo: &Path, git_args: &[OsString]) -> io::Result<i32> {
     let status = Command::new("git")
         .args(git_args)
@@ -209,8 +569,90 @@ fn run_git_in(repo: &Path, git_args: &[OsString]) -> io::Result<i32> {
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
-        .status()?;
-    Ok(status.code().unwrap_or(1))
+        .status();
+    // A repo path can be a declared-but-uninitialized submodule under
+    // --nested, so spawning can fail (missing cwd); report and move on
+    // instead of aborting the whole run, matching how run_parallel handles
+    // a run_git_in_captured error.
+    match status {
+        Ok(status) => Ok(status.code().unwrap_or(1)),
+        Err(e) => {
+            eprintln!("gits: {}: {}", repo.display(), e);
+            Ok(1)
+        }
+    }
+}
+
+/// Run git with stdin disconnected and stdout/stderr captured, for use when
+/// output cannot be streamed directly to the parent's TTY (parallel jobs,
+/// JSON output).
+fn run_git_in_captured(repo: &Path, git_args: &[OsString]) -> io::Result<(i32, String, String)> {
+    let output = Command::new("git")
+        .args(git_args)
+        .current_dir(repo)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()?;
+    let code = output.status.code().unwrap_or(1);
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    Ok((code, stdout, stderr))
+}
+
+/// Dispatch `git_args` across `repos` using up to `job_count` worker threads,
+/// capturing each child's output and handing it to `on_result` in the original
+/// (lexicographic) repo order as soon as every lower-indexed result is ready,
+/// rather than in completion order. Returns the last non-zero exit code, if
+/// any, in that same order.
+///
+/// `on_result` is called once per repo, in order, with that repo's index and
+/// captured `(exit_code, stdout, stderr)`; it is responsible for any heading
+/// and fence printing around the captured output.
+fn run_parallel(
+    repos: &[PathBuf],
+    git_args: &[OsString],
+    job_count: usize,
+    mut on_result: impl FnMut(usize, &(i32, String, String)),
+) -> i32 {
+    let queue: Mutex<VecDeque<(usize, PathBuf)>> =
+        Mutex::new(repos.iter().cloned().enumerate().collect::<VecDeque<_>>());
+    let (tx, rx) = mpsc::channel::<(usize, i32, String, String)>();
+
+    thread::scope(|scope| {
+        for _ in 0..job_count.min(repos.len().max(1)) {
+            let tx = tx.clone();
+            let queue = &queue;
+            scope.spawn(move || loop {
+                let next = queue.lock().unwrap().pop_front();
+                let Some((idx, repo)) = next else {
+                    break;
+                };
+                let (code, stdout, stderr) = run_git_in_captured(&repo, git_args)
+                    .unwrap_or_else(|e| (1, String::new(), e.to_string()));
+                let _ = tx.send((idx, code, stdout, stderr));
+            });
+        }
+        drop(tx);
+
+        let mut slots: Vec<Option<(i32, String, String)>> = (0..repos.len()).map(|_| None).collect();
+        let mut next_to_print = 0usize;
+        let mut last_code = 0i32;
+        for (idx, code, stdout, stderr) in rx {
+            slots[idx] = Some((code, stdout, stderr));
+            while next_to_print < slots.len() {
+                let Some(result) = slots[next_to_print].take() else {
+                    break;
+                };
+                if result.0 != 0 {
+                    last_code = result.0;
+                }
+                on_result(next_to_print, &result);
+                next_to_print += 1;
+            }
+        }
+        last_code
+    })
 }
 
 fn main() -> io::Result<()> {
@@ -232,11 +674,13 @@ fn main() -> io::Result<()> {
             // Remove duplicates while preserving order
             v.dedup();
             v
+        } else if cli.nested {
+            discover_children(&root, cli.max_depth, true)?
         } else {
             vec![root.clone()]
         }
     } else {
-        discover_children(&root, cli.max_depth)?
+        discover_children(&root, cli.max_depth, cli.nested)?
     };
 
     // Stable order: lexicographic
@@ -244,6 +688,13 @@ fn main() -> io::Result<()> {
     // If parent was requested and duplicated roots appeared, ensure unique
     repos.dedup();
 
+    let any_filter = cli.if_dirty || cli.if_clean || cli.on_branch.is_some() || cli.if_ahead || cli.if_behind;
+    repos.retain(|r| matches_filters(r, &cli));
+    if any_filter && repos.is_empty() && cli.format == OutputFormat::Text {
+        println!("No repositories matched the given filters.");
+        return Ok(());
+    }
+
     // Determine git args
     let git_args: Vec<OsString> = if cli.git_args.is_empty() {
         vec![OsString::from("status")]
@@ -251,6 +702,18 @@ fn main() -> io::Result<()> {
         cli.git_args
     };
 
+    // Determine color usage
+    let use_color = match cli.color {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => {
+            let no_color = env::var_os("NO_COLOR").is_some();
+            #[allow(deprecated)]
+            let is_tty = std::io::stdout().is_terminal();
+            is_tty && !no_color
+        }
+    };
+
     if cli.list {
         for r in &repos {
             let head = heading_for(r, &root, cli.absolute_path);
@@ -259,6 +722,10 @@ fn main() -> io::Result<()> {
         return Ok(());
     }
 
+    if cli.summary {
+        return print_summary(&repos, &root, cli.absolute_path, use_color);
+    }
+
     // Decide whether to print headings for single-repo case
     let mut print_headings =
         repos.len() > 1 || cli.parent || cli.absolute_path || cli.root.is_some();
@@ -266,31 +733,52 @@ fn main() -> io::Result<()> {
         print_headings = false;
     }
 
-    // Determine color usage
-    let use_color = match cli.color {
-        ColorMode::Always => true,
-        ColorMode::Never => false,
-        ColorMode::Auto => {
-            let no_color = env::var_os("NO_COLOR").is_some();
-            #[allow(deprecated)]
-            let is_tty = std::io::stdout().is_terminal();
-            is_tty && !no_color
+    let job_count = cli.jobs.resolve();
+    let last_code = if cli.format == OutputFormat::Json {
+        let mut results: Vec<RepoResult> = Vec::with_capacity(repos.len());
+        let code = run_parallel(&repos, &git_args, job_count, |idx, (exit_code, stdout, stderr)| {
+            results.push(RepoResult {
+                path: json_path_label(&repos[idx], &root, cli.absolute_path),
+                exit_code: *exit_code,
+                stdout: stdout.clone(),
+                stderr: stderr.clone(),
+            });
+        });
+        let json = serde_json::to_string_pretty(&results).map_err(io::Error::other)?;
+        println!("{json}");
+        code
+    } else if job_count > 1 {
+        run_parallel(&repos, &git_args, job_count, |idx, (_code, stdout, stderr)| {
+            if print_headings {
+                let head = heading_for(&repos[idx], &root, cli.absolute_path);
+                print_heading(idx, repos.len(), &head, cli.heading_style, use_color);
+            }
+            print!("{}", stdout);
+            eprint!("{}", stderr);
+            let _ = io::stdout().flush();
+            let _ = io::stderr().flush();
+            if print_headings {
+                print_fence(cli.heading_style, use_color);
+            }
+        })
+    } else {
+        let mut last_code = 0i32;
+        for (idx, repo) in repos.iter().enumerate() {
+            if print_headings {
+                let head = heading_for(repo, &root, cli.absolute_path);
+                print_heading(idx, repos.len(), &head, cli.heading_style, use_color);
+            }
+            let code = run_git_in(repo, &git_args)?;
+            if code != 0 {
+                last_code = code;
+            }
+            if print_headings {
+                print_fence(cli.heading_style, use_color);
+            }
         }
+        last_code
     };
 
-    let mut last_code = 0i32;
-    for (idx, repo) in repos.iter().enumerate() {
-        if print_headings {
-            let head = heading_for(repo, &root, cli.absolute_path);
-            print_heading(idx, repos.len(), &head, cli.heading_style, use_color);
-        }
-        let code = run_git_in(repo, &git_args)?;
-        last_code = code;
-        if print_headings {
-            print_fence(cli.heading_style, use_color);
-        }
-    }
-
     // Propagate a failing exit code if any
     if last_code != 0 {
         std::process::exit(last_code);